@@ -0,0 +1,110 @@
+use sha2::{Digest, Sha256};
+use std::io;
+use std::path::Path;
+
+/// No cut point is considered before this offset.
+const MIN_CHUNK_SIZE: usize = 4 * 1024;
+/// Target chunk size; the cut condition loosens (fewer mask bits) once a chunk reaches it (normalized chunking).
+const AVG_CHUNK_SIZE: usize = 16 * 1024;
+/// Hard ceiling: a chunk is cut here even if no gear boundary was found.
+const MAX_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Stricter mask (15 bits, rarer cuts) used below [`AVG_CHUNK_SIZE`], biasing chunks toward the target size from below.
+const MASK_SMALL: u64 = (1 << 15) - 1;
+/// Looser mask (11 bits, more frequent cuts) used at/above [`AVG_CHUNK_SIZE`], biasing chunks toward the target size from above.
+const MASK_LARGE: u64 = (1 << 11) - 1;
+
+/// A content-defined slice of a file: its byte range and its SHA256 id.
+#[derive(Debug, Clone)]
+pub struct ChunkRef {
+    pub id: String,
+    pub offset: usize,
+    pub len: usize,
+}
+
+/// 256-entry table mapping a byte value to a pseudo-random `u64` for the Gear rolling hash, generated once from a fixed seed.
+fn gear_table() -> &'static [u64; 256] {
+    static TABLE: std::sync::OnceLock<[u64; 256]> = std::sync::OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = [0u64; 256];
+        let mut seed: u64 = 0x9E3779B97F4A7C15;
+        for entry in table.iter_mut() {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            *entry = seed;
+        }
+        table
+    })
+}
+
+/// Splits `data` into content-defined chunks using a FastCDC-style rolling
+/// Gear hash with normalized chunking: a stricter mask is used below
+/// [`AVG_CHUNK_SIZE`] and a looser one above it, so boundaries cluster
+/// around the target size. Returns the byte ranges of each chunk; callers
+/// hash each range themselves via [`chunk_id`].
+pub fn cut_boundaries(data: &[u8]) -> Vec<(usize, usize)> {
+    if data.is_empty() {
+        return Vec::new();
+    }
+
+    let gear = gear_table();
+    let mut boundaries = Vec::new();
+    let mut start = 0usize;
+
+    while start < data.len() {
+        let remaining = data.len() - start;
+        if remaining <= MIN_CHUNK_SIZE {
+            boundaries.push((start, data.len()));
+            break;
+        }
+
+        let mut hash: u64 = 0;
+        let mut cut = None;
+        let window_end = remaining.min(MAX_CHUNK_SIZE);
+
+        for i in MIN_CHUNK_SIZE..window_end {
+            let byte = data[start + i];
+            hash = (hash << 1).wrapping_add(gear[byte as usize]);
+            let mask = if i < AVG_CHUNK_SIZE {
+                MASK_SMALL
+            } else {
+                MASK_LARGE
+            };
+            if hash & mask == 0 {
+                cut = Some(i);
+                break;
+            }
+        }
+
+        let end = start + cut.unwrap_or(window_end);
+        boundaries.push((start, end));
+        start = end;
+    }
+
+    boundaries
+}
+
+/// SHA256 of a chunk's bytes, used as both its content id and its
+/// dedup key on the server.
+pub fn chunk_id(bytes: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    format!("{:x}", hasher.finalize())
+}
+
+/// Reads `path` and splits it into content-defined chunks, returning them
+/// in file order along with each chunk's id.
+pub fn split_file(path: &Path) -> io::Result<(Vec<u8>, Vec<ChunkRef>)> {
+    let data = std::fs::read(path)?;
+    let boundaries = cut_boundaries(&data);
+    let chunks = boundaries
+        .into_iter()
+        .map(|(offset, end)| ChunkRef {
+            id: chunk_id(&data[offset..end]),
+            offset,
+            len: end - offset,
+        })
+        .collect();
+    Ok((data, chunks))
+}
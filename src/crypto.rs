@@ -0,0 +1,71 @@
+use chacha20poly1305::aead::{Aead, KeyInit, OsRng};
+use chacha20poly1305::{Key as CipherKey, XChaCha20Poly1305, XNonce};
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use std::io::Write;
+use std::path::Path;
+
+/// 256-bit symmetric key shared by a repo's client(s), persisted next to
+/// `.mynk` so the server is never shown plaintext.
+pub type Key = [u8; 32];
+
+const NONCE_LEN: usize = 24;
+
+/// Loads the repo's key from `.mynk-key`, generating and persisting a
+/// fresh random one the first time `--encrypt` is used.
+pub fn load_or_create_key(root_dir: &Path) -> Result<Key, Box<dyn Error>> {
+    let key_path = root_dir.join(".mynk-key");
+    if let Ok(bytes) = std::fs::read(&key_path) {
+        if bytes.len() == 32 {
+            let mut key = [0u8; 32];
+            key.copy_from_slice(&bytes);
+            return Ok(key);
+        }
+    }
+
+    let key: Key = XChaCha20Poly1305::generate_key(&mut OsRng).into();
+    let mut file = std::fs::File::create_new(&key_path)?;
+    file.write_all(&key)?;
+    Ok(key)
+}
+
+/// Derives a nonce from the key and the chunk's plaintext hash rather
+/// than drawing one at random: encryption must stay deterministic for
+/// the same plaintext so the resulting ciphertext id is stable across
+/// syncs, which is what makes server-side chunk dedup work at all.
+fn derive_nonce(key: &Key, plaintext: &[u8]) -> XNonce {
+    let mut hasher = Sha256::new();
+    hasher.update(key);
+    hasher.update(plaintext);
+    let digest = hasher.finalize();
+    XNonce::clone_from_slice(&digest[..NONCE_LEN])
+}
+
+/// Encrypts a chunk's plaintext, returning the nonce prepended to the
+/// ciphertext. The chunk's server-side identity (and dedup key) is the
+/// hash of this output, not of the plaintext.
+pub fn encrypt_chunk(key: &Key, plaintext: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let nonce = derive_nonce(key, plaintext);
+    let cipher = XChaCha20Poly1305::new(CipherKey::from_slice(key));
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| "chunk encryption failed")?;
+
+    let mut out = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(nonce.as_slice());
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+/// Reverses [`encrypt_chunk`].
+pub fn decrypt_chunk(key: &Key, blob: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    if blob.len() < NONCE_LEN {
+        return Err("encrypted chunk shorter than its nonce".into());
+    }
+    let (nonce_bytes, ciphertext) = blob.split_at(NONCE_LEN);
+    let nonce = XNonce::from_slice(nonce_bytes);
+    let cipher = XChaCha20Poly1305::new(CipherKey::from_slice(key));
+    cipher
+        .decrypt(nonce, ciphertext)
+        .map_err(|_| "chunk decryption failed".into())
+}
@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+/// Cached metadata for one synced file, keyed by its relative path.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CacheEntry {
+    pub chunks: Vec<String>,
+    pub version: i32,
+    pub size: u64,
+    pub mtime_secs: i64,
+}
+
+/// An embedded, incrementally-updated replacement for `.mynk-state.json`,
+/// with each file's cached hash/version/stat under its own key.
+pub struct FileCache {
+    db: sled::Db,
+}
+
+impl FileCache {
+    pub fn open(root_dir: &Path) -> sled::Result<Self> {
+        let db = sled::open(root_dir.join(".mynk-cache"))?;
+        Ok(Self { db })
+    }
+
+    pub fn get(&self, relative_path: &str) -> Option<CacheEntry> {
+        let bytes = self.db.get(relative_path).ok()??;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    pub fn put(&self, relative_path: &str, entry: &CacheEntry) -> Result<(), Box<dyn Error>> {
+        let bytes = serde_json::to_vec(entry)?;
+        self.db.insert(relative_path, bytes)?;
+        Ok(())
+    }
+
+    pub fn remove(&self, relative_path: &str) -> Result<(), Box<dyn Error>> {
+        self.db.remove(relative_path)?;
+        Ok(())
+    }
+
+    pub fn flush(&self) -> Result<(), Box<dyn Error>> {
+        self.db.flush()?;
+        Ok(())
+    }
+
+    /// One-time import of a legacy `.mynk-state.json`. Size and mtime for
+    /// each entry are taken from the file as it exists on disk right now.
+    pub fn migrate_from_json(
+        &self,
+        root_dir: &Path,
+        state_file_path: &Path,
+    ) -> Result<(), Box<dyn Error>> {
+        if !state_file_path.exists() {
+            return Ok(());
+        }
+        let state: serde_json::Value =
+            serde_json::from_reader(std::fs::File::open(state_file_path)?)?;
+        let Some(files) = state.get("files").and_then(|f| f.as_array()) else {
+            return Ok(());
+        };
+
+        for file in files {
+            let Some(filename) = file["filename"].as_str() else {
+                continue;
+            };
+            let chunks: Vec<String> = file["chunks"]
+                .as_array()
+                .map(|a| {
+                    a.iter()
+                        .filter_map(|v| v.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default();
+            let version = file["version"].as_i64().unwrap_or(0) as i32;
+
+            let (size, mtime_secs) = std::fs::metadata(root_dir.join(filename))
+                .and_then(|m| Ok((m.len(), m.modified()?)))
+                .map(|(size, modified)| {
+                    let secs = modified
+                        .duration_since(UNIX_EPOCH)
+                        .map(|d| d.as_secs() as i64)
+                        .unwrap_or(0);
+                    (size, secs)
+                })
+                .unwrap_or((0, 0));
+
+            self.put(
+                filename,
+                &CacheEntry {
+                    chunks,
+                    version,
+                    size,
+                    mtime_secs,
+                },
+            )?;
+        }
+
+        std::fs::remove_file(state_file_path)?;
+        Ok(())
+    }
+}
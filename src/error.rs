@@ -0,0 +1,180 @@
+use reqwest::StatusCode;
+use thiserror::Error;
+
+/// mynk's error type. `Config` covers bad `.mynk`/state setup, `Io` covers
+/// a single file operation (carrying the path and what was attempted),
+/// and `Transport`/`TransportStatus` cover a single HTTP call (carrying
+/// the file, URL, and operation) so a failure reads like "failed to
+/// download <file> at <uri>/chunk/<id>: <cause>" instead of a bare
+/// reqwest error.
+#[derive(Debug, Error)]
+pub enum MynkError {
+    #[error("{0}")]
+    Config(String),
+
+    #[error("failed to {operation} {path}: {source}")]
+    Io {
+        operation: &'static str,
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("failed to {operation} {file} at {url}: {source}")]
+    Transport {
+        operation: &'static str,
+        file: String,
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
+
+    #[error("server rejected {operation} {file} at {url}: {status}")]
+    TransportStatus {
+        operation: &'static str,
+        file: String,
+        url: String,
+        status: StatusCode,
+    },
+
+    #[error("{0}")]
+    Other(String),
+}
+
+impl MynkError {
+    pub fn io(operation: &'static str, path: impl Into<String>, source: std::io::Error) -> Self {
+        MynkError::Io {
+            operation,
+            path: path.into(),
+            source,
+        }
+    }
+
+    pub fn transport(
+        operation: &'static str,
+        file: impl Into<String>,
+        url: impl Into<String>,
+        source: reqwest::Error,
+    ) -> Self {
+        MynkError::Transport {
+            operation,
+            file: file.into(),
+            url: url.into(),
+            source,
+        }
+    }
+
+    pub fn transport_status(
+        operation: &'static str,
+        file: impl Into<String>,
+        url: impl Into<String>,
+        status: StatusCode,
+    ) -> Self {
+        MynkError::TransportStatus {
+            operation,
+            file: file.into(),
+            url: url.into(),
+            status,
+        }
+    }
+
+    /// Whether retrying the request that produced this error stands a
+    /// chance of succeeding: a network hiccup or a 5xx response is worth
+    /// another attempt, but a 4xx or a local I/O failure isn't.
+    fn is_transient(&self) -> bool {
+        match self {
+            MynkError::Transport { .. } => true,
+            MynkError::TransportStatus { status, .. } => status.is_server_error(),
+            _ => false,
+        }
+    }
+}
+
+impl From<std::io::Error> for MynkError {
+    fn from(source: std::io::Error) -> Self {
+        MynkError::io("access", String::new(), source)
+    }
+}
+
+impl From<serde_json::Error> for MynkError {
+    fn from(err: serde_json::Error) -> Self {
+        MynkError::Other(format!("invalid JSON: {err}"))
+    }
+}
+
+impl From<reqwest::Error> for MynkError {
+    fn from(source: reqwest::Error) -> Self {
+        let url = source
+            .url()
+            .map(|u| u.to_string())
+            .unwrap_or_else(String::new);
+        MynkError::transport("request", String::new(), url, source)
+    }
+}
+
+impl From<notify::Error> for MynkError {
+    fn from(err: notify::Error) -> Self {
+        MynkError::Other(format!("filesystem watch error: {err}"))
+    }
+}
+
+impl From<sled::Error> for MynkError {
+    fn from(err: sled::Error) -> Self {
+        MynkError::Config(format!("state store error: {err}"))
+    }
+}
+
+impl From<Box<dyn std::error::Error>> for MynkError {
+    fn from(err: Box<dyn std::error::Error>) -> Self {
+        MynkError::Other(err.to_string())
+    }
+}
+
+impl From<Box<dyn std::error::Error + Send + Sync>> for MynkError {
+    fn from(err: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        MynkError::Other(err.to_string())
+    }
+}
+
+impl From<String> for MynkError {
+    fn from(msg: String) -> Self {
+        MynkError::Other(msg)
+    }
+}
+
+impl From<&str> for MynkError {
+    fn from(msg: &str) -> Self {
+        MynkError::Other(msg.to_string())
+    }
+}
+
+/// Retries `attempt` a bounded number of times with exponential backoff
+/// when its failure looks transient (a network error or a 5xx status).
+/// `operation` is only used to label the warnings printed between
+/// attempts. 4xx responses and local errors fail on the first try since
+/// retrying them wouldn't change the outcome.
+pub async fn retry<T, F, Fut>(operation: &str, mut attempt: F) -> Result<T, MynkError>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, MynkError>>,
+{
+    const MAX_ATTEMPTS: u32 = 3;
+    const BASE_DELAY_MS: u64 = 250;
+
+    let mut last_err = None;
+    for attempt_num in 1..=MAX_ATTEMPTS {
+        match attempt().await {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt_num < MAX_ATTEMPTS && err.is_transient() => {
+                let delay_ms = BASE_DELAY_MS * 2u64.pow(attempt_num - 1);
+                eprintln!(
+                    "{operation} failed (attempt {attempt_num}/{MAX_ATTEMPTS}): {err}; retrying in {delay_ms}ms"
+                );
+                tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+                last_err = Some(err);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    Err(last_err.expect("loop only exits early via a returned Ok or Err"))
+}
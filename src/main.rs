@@ -1,24 +1,52 @@
-use clap::{Arg, Command};
-use reqwest::multipart::{Form, Part};
+mod chunker;
+mod crypto;
+mod db;
+mod error;
+mod mynkignore;
+mod progress;
+mod watcher;
+
+use clap::{Arg, ArgAction, Command};
+use crypto::Key;
+use db::{CacheEntry, FileCache};
+use error::{MynkError, retry};
+use futures_util::StreamExt;
+use mynkignore::MynkIgnore;
+use progress::{ProgressStream, SyncProgress};
+use reqwest::Body;
+use reqwest::header::{HeaderValue, CONTENT_ENCODING};
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
-use sha2::{Digest, Sha256};
-use std::error::Error;
+use std::collections::HashSet;
 use std::fs::{self, File};
-use std::io::{Read, Write};
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use tokio::fs as tokio_fs;
 use walkdir::WalkDir;
 
+/// A synced file's manifest: its content is the concatenation of `chunks`
+/// in order. Two entries are identical in content iff their chunk lists
+/// match, so the chunk list doubles as the whole-file content hash. When
+/// encryption is on, each id is the hash of that chunk's ciphertext, so
+/// the server never sees a hash of the plaintext.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct FileEntry {
     filename: String,
-    hash: String,
+    chunks: Vec<String>,
     version: i32,
 }
 
+/// The `.mynk` file's contents: which server to sync with, and whether
+/// file bodies are encrypted before they leave this machine.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct Config {
+    uri: String,
+    #[serde(default)]
+    encrypt: bool,
+}
+
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
+async fn main() -> Result<(), MynkError> {
     let matches = Command::new("mynk")
         .about("Synchronizes directory with server")
         .version("0.1.0")
@@ -35,50 +63,132 @@ async fn main() -> Result<(), Box<dyn Error>> {
                         .value_name("URI")
                         .help("Server URI for synchronization")
                         .required(true),
-                ),
+                )
+                .arg(
+                    Arg::new("encrypt")
+                        .long("encrypt")
+                        .help("Encrypt file contents client-side before upload")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(quiet_arg())
+                .arg(progress_arg()),
         )
         .subcommand(
             Command::new("sync")
                 .short_flag('S')
                 .long_flag("sync")
-                .about("Synchronize directory with server"),
+                .about("Synchronize directory with server")
+                .arg(quiet_arg())
+                .arg(progress_arg()),
+        )
+        .subcommand(
+            Command::new("watch")
+                .short_flag('W')
+                .long_flag("watch")
+                .about("Watch the directory and sync changes continuously")
+                .arg(quiet_arg())
+                .arg(progress_arg())
+                .arg(
+                    Arg::new("debounce-ms")
+                        .long("debounce-ms")
+                        .value_name("MS")
+                        .help("Milliseconds to wait after the last event in a burst")
+                        .value_parser(clap::value_parser!(u64))
+                        .default_value(watcher::DEFAULT_DEBOUNCE_MS.to_string()),
+                ),
         )
         .get_matches();
 
     match matches.subcommand() {
         Some(("init", init_matches)) => {
             let uri = init_matches.get_one::<String>("uri").unwrap();
+            let encrypt = init_matches.get_flag("encrypt");
+            let quiet = init_matches.get_flag("quiet") && !init_matches.get_flag("progress");
             println!("Initializing for URI: {}", uri);
-            create_root(uri)?;
+            create_root(uri, encrypt)?;
             create_root_state()?;
-            sync_files(uri).await?;
+            let key = encrypt_key_for(Path::new("."), encrypt)?;
+            sync_files(uri, quiet, key.as_ref()).await?;
         }
-        Some(("sync", _)) => {
+        Some(("sync", sync_matches)) => {
             println!("Syncing directory...");
+            let quiet = sync_matches.get_flag("quiet") && !sync_matches.get_flag("progress");
             let mynk_path = find_mynk_root();
             if let Some(path) = mynk_path {
-                let uri = fs::read_to_string(&path)?;
-                sync_files(&uri).await?;
+                let config = load_config(&path)?;
+                let root_dir = path.parent().ok_or("Invalid .mynk path")?;
+                let key = encrypt_key_for(root_dir, config.encrypt)?;
+                sync_files(&config.uri, quiet, key.as_ref()).await?;
             } else {
                 return Err("No .mynk file found. Run 'mynk init --uri <URI>' first.".into());
             }
         }
+        Some(("watch", watch_matches)) => {
+            let quiet = watch_matches.get_flag("quiet") && !watch_matches.get_flag("progress");
+            let debounce_ms = *watch_matches.get_one::<u64>("debounce-ms").unwrap();
+            let root_dir =
+                find_mynk_root_dir().ok_or("No .mynk file found. Run 'mynk init --uri <URI>' first.")?;
+            let config = load_config(&root_dir.join(".mynk"))?;
+            let key = encrypt_key_for(&root_dir, config.encrypt)?;
+            sync_files(&config.uri, quiet, key.as_ref()).await?;
+            watcher::watch(
+                &config.uri,
+                root_dir,
+                quiet,
+                std::time::Duration::from_millis(debounce_ms),
+                key,
+            )
+            .await?;
+        }
         _ => unreachable!(),
     }
 
     Ok(())
 }
 
-fn create_root(uri: &str) -> std::io::Result<()> {
-    let mut file = File::create_new(".mynk")?;
-    write!(file, "{}", uri)?;
+fn quiet_arg() -> Arg {
+    Arg::new("quiet")
+        .long("quiet")
+        .help("Suppress progress bars")
+        .action(ArgAction::SetTrue)
+}
+
+fn progress_arg() -> Arg {
+    Arg::new("progress")
+        .long("progress")
+        .help("Force progress bars even if --quiet was also passed")
+        .action(ArgAction::SetTrue)
+}
+
+fn create_root(uri: &str, encrypt: bool) -> Result<(), MynkError> {
+    let config = Config {
+        uri: uri.to_string(),
+        encrypt,
+    };
+    let mut file =
+        File::create_new(".mynk").map_err(|source| MynkError::io("create", ".mynk", source))?;
+    write!(file, "{}", serde_json::to_string_pretty(&config)?)?;
     Ok(())
 }
 
-fn create_root_state() -> std::io::Result<()> {
-    let state = json!({ "files": [] });
-    let mut file = File::create_new(".mynk-state.json")?;
-    write!(file, "{}", state)?;
+fn load_config(mynk_path: &Path) -> Result<Config, MynkError> {
+    let contents = fs::read_to_string(mynk_path)
+        .map_err(|source| MynkError::io("read", mynk_path.display().to_string(), source))?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+/// Returns the repo's encryption key when `encrypt` mode is on,
+/// generating and persisting one next to `.mynk` the first time.
+fn encrypt_key_for(root_dir: &Path, encrypt: bool) -> Result<Option<Key>, MynkError> {
+    if encrypt {
+        Ok(Some(crypto::load_or_create_key(root_dir)?))
+    } else {
+        Ok(None)
+    }
+}
+
+fn create_root_state() -> Result<(), MynkError> {
+    FileCache::open(Path::new("."))?;
     Ok(())
 }
 
@@ -104,86 +214,121 @@ fn find_mynk_root_dir() -> Option<PathBuf> {
     None
 }
 
-fn find_mynk_state_root() -> Option<PathBuf> {
-    let current_dir = std::env::current_dir().ok()?;
-    for dir in current_dir.ancestors() {
-        let candidate = dir.join(".mynk-state.json");
-        if candidate.exists() {
-            return Some(candidate);
-        }
-    }
-    None
-}
-
-fn compute_file_hash(path: &Path) -> std::io::Result<String> {
-    let mut file = File::open(path)?;
-    let mut hasher = Sha256::new();
-    let mut buffer = [0; 4096];
-    loop {
-        let bytes_read = file.read(&mut buffer)?;
-        if bytes_read == 0 {
-            break;
-        }
-        hasher.update(&buffer[..bytes_read]);
-    }
-    Ok(format!("{:x}", hasher.finalize()))
-}
-
-fn build_local_state(root_dir: &Path) -> std::io::Result<Vec<FileEntry>> {
+/// Builds the local file list, reusing a file's cached chunk ids from
+/// `cache` whenever its size and mtime match what was last recorded —
+/// only files that actually changed get re-hashed. When `encrypt_key` is
+/// set, the ids recorded are hashes of each chunk's ciphertext (the
+/// server-facing identity); plaintext is still what gets chunked so
+/// content-defined boundaries stay stable across edits. Paths matched by
+/// `ignore` (compiled from `.mynkignore`) are skipped entirely, same as
+/// mynk's own bookkeeping files.
+fn build_local_state(
+    root_dir: &Path,
+    cache: &FileCache,
+    encrypt_key: Option<&Key>,
+    ignore: &MynkIgnore,
+) -> Result<Vec<FileEntry>, MynkError> {
     let mut files = Vec::new();
-    let state_file_path = root_dir.join(".mynk-state.json");
     let mynk_file = root_dir.join(".mynk");
-
-    for entry in WalkDir::new(root_dir).into_iter().filter_map(|e| e.ok()) {
+    let mynk_key_file = root_dir.join(".mynk-key");
+    let mynkignore_file = root_dir.join(".mynkignore");
+    let cache_dir = root_dir.join(".mynk-cache");
+
+    let walker = WalkDir::new(root_dir).into_iter().filter_entry(|e| {
+        e.path() != cache_dir && !mynkignore::is_ignored(ignore, e.path(), e.file_type().is_dir())
+    });
+    for entry in walker.filter_map(|e| e.ok()) {
         let path = entry.path();
-        if path.is_file() && path != state_file_path && path != mynk_file {
-            let relative_path = path
-                .strip_prefix(root_dir)
-                .unwrap()
-                .to_string_lossy()
-                .replace('\\', "/");
-            let hash = compute_file_hash(path)?;
-            files.push(FileEntry {
-                filename: relative_path,
-                hash,
-                version: 0,
-            });
+        if !path.is_file() || path == mynk_file || path == mynk_key_file || path == mynkignore_file
+        {
+            continue;
         }
-    }
-
-    let state_file_path = root_dir.join(".mynk-state.json");
-    if state_file_path.exists() {
-        let state: Value = serde_json::from_reader(File::open(&state_file_path)?)?;
-        if let Some(existing_files) = state.get("files").and_then(|f| f.as_array()) {
-            for file in files.iter_mut() {
-                if let Some(existing) = existing_files
-                    .iter()
-                    .find(|e| e["filename"].as_str() == Some(&file.filename))
-                {
-                    file.version = existing["version"].as_i64().unwrap_or(0) as i32;
-                }
+        let relative_path = path
+            .strip_prefix(root_dir)
+            .unwrap()
+            .to_string_lossy()
+            .replace('\\', "/");
+
+        let metadata =
+            fs::metadata(path).map_err(|source| MynkError::io("stat", &relative_path, source))?;
+        let size = metadata.len();
+        let mtime_secs = metadata
+            .modified()
+            .map_err(|source| MynkError::io("stat", &relative_path, source))?
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+
+        let cached = cache.get(&relative_path);
+        let (chunks, version) = match &cached {
+            Some(entry) if entry.size == size && entry.mtime_secs == mtime_secs => {
+                (entry.chunks.clone(), entry.version)
             }
-        }
+            _ => {
+                let (data, chunk_refs) = chunker::split_file(path)
+                    .map_err(|source| MynkError::io("read", &relative_path, source))?;
+                let chunks = identity_ids(&data, &chunk_refs, encrypt_key)?;
+                let version = cached.map(|e| e.version).unwrap_or(0);
+                cache.put(
+                    &relative_path,
+                    &CacheEntry {
+                        chunks: chunks.clone(),
+                        version,
+                        size,
+                        mtime_secs,
+                    },
+                )?;
+                (chunks, version)
+            }
+        };
+
+        files.push(FileEntry {
+            filename: relative_path,
+            chunks,
+            version,
+        });
     }
 
     Ok(files)
 }
 
-async fn sync_files(uri: &str) -> Result<(), Box<dyn Error>> {
-    let root_dir = find_mynk_root_dir().ok_or("Could not find .mynk root")?;
-    let state_file_path = find_mynk_state_root().ok_or("Could not find .mynk-state.json")?;
-
-    let local_files = build_local_state(&root_dir)?;
-
+/// Fetches and parses the server's `/structure` manifest. Shared by
+/// `sync_files` and the watch loop's periodic reconciliation.
+async fn fetch_server_files(uri: &str) -> Result<Vec<FileEntry>, MynkError> {
     let client = reqwest::Client::new();
-    let server_state: Value = client
-        .get(format!("{}/structure", uri))
+    let structure_url = format!("{}/structure", uri);
+    let response = client
+        .get(&structure_url)
         .send()
-        .await?
-        .error_for_status()?
+        .await
+        .map_err(|source| MynkError::transport("fetch", "<structure>", &structure_url, source))?;
+    if !response.status().is_success() {
+        return Err(MynkError::transport_status(
+            "fetch",
+            "<structure>",
+            &structure_url,
+            response.status(),
+        ));
+    }
+    let server_state: Value = response
         .json()
-        .await?;
-    let server_files: Vec<FileEntry> = serde_json::from_value(server_state["files"].clone())?;
+        .await
+        .map_err(|source| MynkError::transport("parse", "<structure>", &structure_url, source))?;
+    Ok(serde_json::from_value(server_state["files"].clone())?)
+}
+
+async fn sync_files(
+    uri: &str,
+    quiet: bool,
+    encrypt_key: Option<&Key>,
+) -> Result<(), MynkError> {
+    let root_dir = find_mynk_root_dir().ok_or("Could not find .mynk root")?;
+    let cache = FileCache::open(&root_dir)?;
+    cache.migrate_from_json(&root_dir, &root_dir.join(".mynk-state.json"))?;
+
+    let ignore = mynkignore::load(&root_dir);
+    let local_files = build_local_state(&root_dir, &cache, encrypt_key, &ignore)?;
+    let server_files = fetch_server_files(uri).await?;
 
     let mut local_files_map: std::collections::HashMap<String, FileEntry> = local_files
         .clone()
@@ -196,6 +341,7 @@ async fn sync_files(uri: &str) -> Result<(), Box<dyn Error>> {
         .collect();
 
     let mut new_local_state = Vec::new();
+    let mut to_download = Vec::new();
     let mut to_upload = Vec::new();
     let mut to_delete_server: Vec<String> = Vec::new();
     let mut staged_files = Vec::new();
@@ -204,10 +350,10 @@ async fn sync_files(uri: &str) -> Result<(), Box<dyn Error>> {
         if let Some(local_file) = local_files_map.remove(filename) {
             if server_file.version > local_file.version {
                 println!("Server has newer version for {}. Downloading.", filename);
-                download_file(uri, &root_dir, filename).await?;
+                to_download.push(server_file.clone());
                 new_local_state.push(server_file.clone());
             } else if server_file.version == local_file.version
-                && local_file.hash != server_file.hash
+                && local_file.chunks != server_file.chunks
             {
                 println!(
                     "Local changes detected for {}. Queuing for upload.",
@@ -217,14 +363,14 @@ async fn sync_files(uri: &str) -> Result<(), Box<dyn Error>> {
                 new_local_state.push(local_file.clone());
             } else {
                 new_local_state.push(local_file.clone());
-                if local_file.hash != server_file.hash {
+                if local_file.chunks != server_file.chunks {
                     println!("Local file {} is newer. Queuing for upload.", filename);
                     to_upload.push(local_file.clone());
                 }
             }
         } else {
             println!("New file on server: {}. Downloading.", filename);
-            download_file(uri, &root_dir, filename).await?;
+            to_download.push(server_file.clone());
             new_local_state.push(server_file.clone());
         }
     }
@@ -239,106 +385,375 @@ async fn sync_files(uri: &str) -> Result<(), Box<dyn Error>> {
             new_local_state.push(local_file.clone());
         } else {
             println!("Deleting local file not on server: {}", filename);
-            tokio_fs::remove_file(root_dir.join(&filename)).await?;
+            tokio_fs::remove_file(root_dir.join(&filename))
+                .await
+                .map_err(|source| MynkError::io("delete", &filename, source))?;
+            cache.remove(&filename)?;
+        }
+    }
+
+    // Totals for uploads are known up front from local file sizes; a
+    // download's size isn't known until each chunk's response headers
+    // arrive, so the aggregate bar's length grows via `grow_aggregate`
+    // as those come in instead of being included here.
+    let mut total_bytes = 0u64;
+    for file in to_upload.iter().chain(staged_files.iter()) {
+        total_bytes += fs::metadata(root_dir.join(&file.filename))
+            .map(|m| m.len())
+            .unwrap_or(0);
+    }
+    let sync_progress = SyncProgress::new(quiet, total_bytes);
+
+    // A failure on one file shouldn't stop the rest of the tree from
+    // syncing; failures are collected and reported once everything that
+    // could proceed has. `failed_filenames` keeps a file that failed to
+    // download from being mistaken for one that's genuinely absent from
+    // both sides further down, where that would otherwise get it deleted
+    // from the server.
+    let mut errors: Vec<MynkError> = Vec::new();
+    let mut failed_filenames: HashSet<String> = HashSet::new();
+
+    for file in to_download.iter() {
+        let result = retry("download", || {
+            download_file(uri, &root_dir, file, &sync_progress, encrypt_key)
+        })
+        .await;
+        if let Err(err) = result {
+            eprintln!("Failed to download {}: {}", file.filename, err);
+            errors.push(err);
+            failed_filenames.insert(file.filename.clone());
+            new_local_state.retain(|f| f.filename != file.filename);
         }
     }
 
     for file in to_upload.iter() {
-        upload_file(uri, &root_dir, file).await?;
+        let result = retry("upload", || {
+            upload_file(uri, &root_dir, file, &sync_progress, encrypt_key)
+        })
+        .await;
         new_local_state.retain(|f| f.filename != file.filename);
-        let updated_file = FileEntry {
-            filename: file.filename.clone(),
-            hash: file.hash.clone(),
-            version: file.version + 1,
-        };
-        new_local_state.push(updated_file);
+        match result {
+            Ok(()) => new_local_state.push(FileEntry {
+                filename: file.filename.clone(),
+                chunks: file.chunks.clone(),
+                version: file.version + 1,
+            }),
+            Err(err) => {
+                eprintln!("Failed to upload {}: {}", file.filename, err);
+                errors.push(err);
+                failed_filenames.insert(file.filename.clone());
+            }
+        }
     }
 
     for file in staged_files {
-        upload_file(uri, &root_dir, &file).await?;
+        let result = retry("upload", || {
+            upload_file(uri, &root_dir, &file, &sync_progress, encrypt_key)
+        })
+        .await;
         new_local_state.retain(|f| f.filename != file.filename);
-        let updated_file = FileEntry {
-            filename: file.filename.clone(),
-            hash: file.hash.clone(),
-            version: 1,
-        };
-        new_local_state.push(updated_file);
+        match result {
+            Ok(()) => new_local_state.push(FileEntry {
+                filename: file.filename.clone(),
+                chunks: file.chunks.clone(),
+                version: 1,
+            }),
+            Err(err) => {
+                eprintln!("Failed to upload {}: {}", file.filename, err);
+                errors.push(err);
+                failed_filenames.insert(file.filename.clone());
+            }
+        }
     }
 
     for (filename, _server_file) in server_files_map {
         if !local_files.iter().any(|f| f.filename == filename)
             && !new_local_state.iter().any(|f| f.filename == filename)
+            && !failed_filenames.contains(&filename)
         {
             println!("Deleting file on server not present locally: {}", filename);
             to_delete_server.push(filename.clone());
         }
     }
     for filename in to_delete_server {
-        delete_file(uri, &filename).await?;
+        if let Err(err) = retry("delete", || delete_file(uri, &filename)).await {
+            eprintln!("Failed to delete {}: {}", filename, err);
+            errors.push(err);
+        }
     }
 
-    let state = json!({ "files": new_local_state });
-    tokio_fs::write(&state_file_path, serde_json::to_string_pretty(&state)?).await?;
-
-    Ok(())
+    for file in &new_local_state {
+        let path = root_dir.join(&file.filename);
+        let Ok(metadata) = fs::metadata(&path) else {
+            continue;
+        };
+        let mtime_secs = metadata
+            .modified()
+            .map_err(|source| MynkError::io("stat", &file.filename, source))?
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        cache.put(
+            &file.filename,
+            &CacheEntry {
+                chunks: file.chunks.clone(),
+                version: file.version,
+                size: metadata.len(),
+                mtime_secs,
+            },
+        )?;
+    }
+    cache.flush()?;
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(MynkError::Other(format!(
+            "sync finished with {} error(s):\n{}",
+            errors.len(),
+            errors
+                .iter()
+                .map(|e| format!("  - {e}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        )))
+    }
 }
 
-async fn download_file(uri: &str, root_dir: &Path, filename: &str) -> Result<(), Box<dyn Error>> {
+/// Downloads a file by fetching each chunk in `server_file.chunks` in
+/// order and concatenating them, rather than transferring the whole file
+/// body in one request. Each chunk is streamed as a raw (optionally
+/// zstd-compressed) body rather than wrapped in a JSON envelope, so
+/// binary chunks survive untouched.
+async fn download_file(
+    uri: &str,
+    root_dir: &Path,
+    server_file: &FileEntry,
+    sync_progress: &SyncProgress,
+    encrypt_key: Option<&Key>,
+) -> Result<(), MynkError> {
     let client = reqwest::Client::new();
-    let response = client
-        .get(format!("{}/file/{}", uri, filename))
-        .send()
-        .await?
-        .error_for_status()?;
+    let bar = sync_progress.start_file(&server_file.filename, 0);
+    let mut contents = Vec::new();
+    for chunk_id in &server_file.chunks {
+        let chunk_url = format!("{}/chunk/{}", uri, chunk_id);
+        let response = client
+            .get(&chunk_url)
+            .send()
+            .await
+            .map_err(|source| {
+                MynkError::transport("download", &server_file.filename, &chunk_url, source)
+            })?;
+        if !response.status().is_success() {
+            return Err(MynkError::transport_status(
+                "download",
+                &server_file.filename,
+                &chunk_url,
+                response.status(),
+            ));
+        }
+        let is_zstd = response
+            .headers()
+            .get(CONTENT_ENCODING)
+            .map(|v| v.as_bytes() == b"zstd")
+            .unwrap_or(false);
+        if let Some(len) = response.content_length() {
+            sync_progress.grow_aggregate(len);
+        }
+        let mut stream = ProgressStream::new(
+            response.bytes_stream(),
+            bar.clone(),
+            sync_progress.aggregate(),
+        );
+        let mut chunk_bytes = Vec::new();
+        while let Some(piece) = stream.next().await {
+            let piece = piece.map_err(|source| {
+                MynkError::transport("download", &server_file.filename, &chunk_url, source)
+            })?;
+            chunk_bytes.extend_from_slice(&piece);
+        }
+        let decompressed = if is_zstd {
+            zstd::stream::decode_all(chunk_bytes.as_slice())
+                .map_err(|source| MynkError::io("decompress", &server_file.filename, source))?
+        } else {
+            chunk_bytes
+        };
+        let plaintext = match encrypt_key {
+            Some(key) => crypto::decrypt_chunk(key, &decompressed)?,
+            None => decompressed,
+        };
+        contents.extend_from_slice(&plaintext);
+    }
+    sync_progress.finish_file(bar);
+
+    let file_path = root_dir.join(&server_file.filename);
+    let parent = file_path
+        .parent()
+        .ok_or_else(|| MynkError::Config(format!("invalid file path: {}", server_file.filename)))?;
+    tokio_fs::create_dir_all(parent)
+        .await
+        .map_err(|source| MynkError::io("create directory for", &server_file.filename, source))?;
+    tokio_fs::write(&file_path, contents)
+        .await
+        .map_err(|source| MynkError::io("write", &server_file.filename, source))?;
+    println!("Downloaded file: {}", server_file.filename);
 
-    let content = response.json::<Value>().await?;
-    let file_content = content["contents"]
-        .as_str()
-        .ok_or("Invalid file content response")?;
+    Ok(())
+}
 
-    let file_path = root_dir.join(filename);
-    tokio_fs::create_dir_all(file_path.parent().ok_or("Invalid file path")?).await?;
-    tokio_fs::write(&file_path, file_content).await?;
-    println!("Downloaded file: {}", filename);
+/// The server-facing identity of each chunk: its plaintext hash
+/// (`chunk.id`) when encryption is off, or the hash of its ciphertext
+/// when an `encrypt_key` is set, so the server only ever indexes and
+/// stores ciphertext.
+fn identity_ids(
+    data: &[u8],
+    chunk_refs: &[chunker::ChunkRef],
+    encrypt_key: Option<&Key>,
+) -> Result<Vec<String>, MynkError> {
+    chunk_refs
+        .iter()
+        .map(|chunk| match encrypt_key {
+            Some(key) => {
+                let plaintext = &data[chunk.offset..chunk.offset + chunk.len];
+                let ciphertext = crypto::encrypt_chunk(key, plaintext)?;
+                Ok(chunker::chunk_id(&ciphertext))
+            }
+            None => Ok(chunk.id.clone()),
+        })
+        .collect()
+}
 
-    Ok(())
+/// Probes the server for which of `ids` it already has, returning only
+/// the ones it's missing.
+async fn fetch_missing_chunk_ids(
+    client: &reqwest::Client,
+    uri: &str,
+    filename: &str,
+    ids: &[String],
+) -> Result<HashSet<String>, MynkError> {
+    let missing_url = format!("{}/chunks/missing", uri);
+    let response = client
+        .post(&missing_url)
+        .json(&json!({ "ids": ids }))
+        .send()
+        .await
+        .map_err(|source| MynkError::transport("probe", filename, &missing_url, source))?;
+    if !response.status().is_success() {
+        return Err(MynkError::transport_status(
+            "probe",
+            filename,
+            &missing_url,
+            response.status(),
+        ));
+    }
+    let missing: Vec<String> = response
+        .json()
+        .await
+        .map_err(|source| MynkError::transport("parse", filename, &missing_url, source))?;
+    Ok(missing.into_iter().collect())
 }
 
-async fn upload_file(uri: &str, root_dir: &Path, file: &FileEntry) -> Result<(), Box<dyn Error>> {
+/// Splits the file into content-defined chunks, asks the server which
+/// ones it's missing, and uploads only those, rather than sending the
+/// whole file body on every change. Each chunk is zstd-compressed and
+/// sent as a raw body with its filename/hash/version carried in headers,
+/// rather than as JSON or a multipart form.
+async fn upload_file(
+    uri: &str,
+    root_dir: &Path,
+    file: &FileEntry,
+    sync_progress: &SyncProgress,
+    encrypt_key: Option<&Key>,
+) -> Result<(), MynkError> {
     let file_path = root_dir.join(&file.filename);
-    let file_content = tokio_fs::read(&file_path).await?;
-    let part = Part::bytes(file_content).file_name(file.filename.clone());
-    let form = Form::new().part("file", part);
+    let (data, chunk_refs) = chunker::split_file(&file_path)
+        .map_err(|source| MynkError::io("read", &file.filename, source))?;
 
     let client = reqwest::Client::new();
-    let response = client
-        .post(format!("{}/upload", uri))
-        .multipart(form)
-        .send()
-        .await?
-        .error_for_status()?;
+    // `file.chunks` already carries each chunk's server-facing identity
+    // (ciphertext hash when encrypted), computed by `build_local_state`.
+    let missing = fetch_missing_chunk_ids(&client, uri, &file.filename, &file.chunks).await?;
+
+    // Both bars track plaintext bytes, matching `total_bytes` (main.rs),
+    // which is computed from on-disk (plaintext) file sizes — so a
+    // skipped, already-on-server chunk advances them exactly as far as an
+    // uploaded one, and compression/encryption never show up in the count.
+    let bar = sync_progress.start_file(&file.filename, data.len() as u64);
+    for (chunk, chunk_id) in chunk_refs.iter().zip(file.chunks.iter()) {
+        if !missing.contains(chunk_id) {
+            bar.inc(chunk.len as u64);
+            if let Some(aggregate) = sync_progress.aggregate() {
+                aggregate.inc(chunk.len as u64);
+            }
+            continue;
+        }
+        let plaintext = &data[chunk.offset..chunk.offset + chunk.len];
+        let body_bytes = match encrypt_key {
+            Some(key) => crypto::encrypt_chunk(key, plaintext)?,
+            None => plaintext.to_vec(),
+        };
+        let compressed = zstd::stream::encode_all(body_bytes.as_slice(), 0)
+            .map_err(|source| MynkError::io("compress", &file.filename, source))?;
+        let chunk_url = format!("{}/chunk/{}", uri, chunk_id);
+        let response = client
+            .post(&chunk_url)
+            .header(CONTENT_ENCODING, HeaderValue::from_static("zstd"))
+            .header("x-mynk-filename", &file.filename)
+            .header("x-mynk-hash", chunk_id)
+            .header("x-mynk-version", file.version.to_string())
+            .body(Body::from(compressed))
+            .send()
+            .await
+            .map_err(|source| MynkError::transport("upload", &file.filename, &chunk_url, source))?;
+        if !response.status().is_success() {
+            return Err(MynkError::transport_status(
+                "upload",
+                &file.filename,
+                &chunk_url,
+                response.status(),
+            ));
+        }
+        bar.inc(chunk.len as u64);
+        if let Some(aggregate) = sync_progress.aggregate() {
+            aggregate.inc(chunk.len as u64);
+        }
+    }
+    sync_progress.finish_file(bar);
 
-    let resp_json: Value = response.json().await?;
     println!(
-        "Uploaded file: {}. Server response: {}",
-        file.filename, resp_json
+        "Uploaded file: {} ({} of {} chunks were new)",
+        file.filename,
+        missing.len(),
+        chunk_refs.len()
     );
     Ok(())
 }
 
-async fn delete_file(uri: &str, filename: &str) -> Result<(), Box<dyn Error>> {
+async fn delete_file(uri: &str, filename: &str) -> Result<(), MynkError> {
     let client = reqwest::Client::new();
+    let delete_url = format!("{}/delete", uri);
     let response = client
-        .delete(format!("{}/delete", uri))
+        .delete(&delete_url)
         .json(&json!({ "filename": filename }))
         .send()
-        .await?
-        .error_for_status()?;
+        .await
+        .map_err(|source| MynkError::transport("delete", filename, &delete_url, source))?;
+    if !response.status().is_success() {
+        return Err(MynkError::transport_status(
+            "delete",
+            filename,
+            &delete_url,
+            response.status(),
+        ));
+    }
 
     println!(
         "Deleted file on server: {}. Server response: {}",
         filename,
-        response.json::<Value>().await?
+        response
+            .json::<Value>()
+            .await
+            .map_err(|source| MynkError::transport("parse", filename, &delete_url, source))?
     );
     Ok(())
 }
@@ -0,0 +1,101 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Every `.mynkignore` found under a root directory, each compiled with its
+/// own containing directory, so a nested file's patterns (e.g.
+/// `subdir/.mynkignore` containing `build/`) are matched relative to
+/// `subdir/` rather than the tree root.
+pub struct MynkIgnore {
+    /// (directory the `.mynkignore` lives in, its compiled matcher),
+    /// shallowest first so callers can check from the root down.
+    matchers: Vec<(std::path::PathBuf, Gitignore)>,
+}
+
+pub fn load(root_dir: &Path) -> MynkIgnore {
+    let mut matchers = Vec::new();
+    for entry in WalkDir::new(root_dir).into_iter().filter_map(|e| e.ok()) {
+        if entry.file_name() != ".mynkignore" {
+            continue;
+        }
+        let dir = entry
+            .path()
+            .parent()
+            .unwrap_or(root_dir)
+            .to_path_buf();
+        let mut builder = GitignoreBuilder::new(&dir);
+        if let Some(err) = builder.add(entry.path()) {
+            eprintln!(
+                "Warning: failed to parse {}: {}",
+                entry.path().display(),
+                err
+            );
+            continue;
+        }
+        match builder.build() {
+            Ok(matcher) => matchers.push((dir, matcher)),
+            Err(err) => eprintln!(
+                "Warning: failed to compile {}: {}",
+                entry.path().display(),
+                err
+            ),
+        }
+    }
+    matchers.sort_by_key(|(dir, _)| dir.components().count());
+    MynkIgnore { matchers }
+}
+
+/// Checks `path` against every `.mynkignore` whose directory is an
+/// ancestor of it, shallowest first, so a deeper directory's pattern
+/// (including a `!negation`) has the final say over a shallower one's —
+/// matching how nested gitignore files stack.
+pub fn is_ignored(matcher: &MynkIgnore, path: &Path, is_dir: bool) -> bool {
+    let mut ignored = false;
+    for (dir, gitignore) in &matcher.matchers {
+        if !path.starts_with(dir) {
+            continue;
+        }
+        match gitignore.matched(path, is_dir) {
+            ignore::Match::Ignore(_) => ignored = true,
+            ignore::Match::Whitelist(_) => ignored = false,
+            ignore::Match::None => {}
+        }
+    }
+    ignored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_root(name: &str) -> std::path::PathBuf {
+        let root = std::env::temp_dir().join(format!("mynkignore_test_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("subdir")).unwrap();
+        root
+    }
+
+    #[test]
+    fn shallow_ignore_is_overridden_by_deep_negate() {
+        let root = temp_root("shallow_ignore_deep_negate");
+        std::fs::write(root.join(".mynkignore"), "build/\n").unwrap();
+        std::fs::write(root.join("subdir/.mynkignore"), "!build/\n").unwrap();
+
+        let matcher = load(&root);
+        assert!(!is_ignored(&matcher, &root.join("subdir/build"), true));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn shallow_negate_is_overridden_by_deep_ignore() {
+        let root = temp_root("shallow_negate_deep_ignore");
+        std::fs::write(root.join(".mynkignore"), "build/\n!subdir/build/\n").unwrap();
+        std::fs::write(root.join("subdir/.mynkignore"), "build/\n").unwrap();
+
+        let matcher = load(&root);
+        assert!(is_ignored(&matcher, &root.join("subdir/build"), true));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
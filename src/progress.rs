@@ -0,0 +1,106 @@
+use bytes::Bytes;
+use futures_util::Stream;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Wraps a byte stream, incrementing a progress bar by the length of
+/// each chunk as it flows through.
+pub struct ProgressStream<S> {
+    inner: S,
+    bar: ProgressBar,
+    aggregate: Option<ProgressBar>,
+}
+
+impl<S> ProgressStream<S> {
+    pub fn new(inner: S, bar: ProgressBar, aggregate: Option<ProgressBar>) -> Self {
+        Self {
+            inner,
+            bar,
+            aggregate,
+        }
+    }
+}
+
+impl<S, E> Stream for ProgressStream<S>
+where
+    S: Stream<Item = Result<Bytes, E>> + Unpin,
+{
+    type Item = Result<Bytes, E>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match Pin::new(&mut self.inner).poll_next(cx) {
+            Poll::Ready(Some(Ok(chunk))) => {
+                self.bar.inc(chunk.len() as u64);
+                if let Some(aggregate) = &self.aggregate {
+                    aggregate.inc(chunk.len() as u64);
+                }
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            other => other,
+        }
+    }
+}
+
+/// Tracks progress across an entire `sync`: a per-file bar for whichever
+/// transfer is in flight, plus one aggregate bar spanning every queued
+/// upload/download. Both are `None` when `quiet` is set.
+pub struct SyncProgress {
+    multi: Option<MultiProgress>,
+    aggregate: Option<ProgressBar>,
+}
+
+impl SyncProgress {
+    pub fn new(quiet: bool, total_bytes: u64) -> Self {
+        if quiet {
+            return Self {
+                multi: None,
+                aggregate: None,
+            };
+        }
+        let multi = MultiProgress::new();
+        let aggregate = multi.add(ProgressBar::new(total_bytes));
+        aggregate.set_style(
+            ProgressStyle::with_template(
+                "{msg} [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})",
+            )
+            .unwrap(),
+        );
+        aggregate.set_message("total");
+        Self {
+            multi: Some(multi),
+            aggregate: Some(aggregate),
+        }
+    }
+
+    /// Adds a bar for a single file's transfer. Pass `0` for `size_hint`
+    /// when the size isn't known up front.
+    pub fn start_file(&self, filename: &str, size_hint: u64) -> ProgressBar {
+        let Some(multi) = &self.multi else {
+            return ProgressBar::hidden();
+        };
+        let bar = multi.add(ProgressBar::new(size_hint));
+        bar.set_style(
+            ProgressStyle::with_template("{msg} [{bar:30.green/blue}] {bytes}/{total_bytes}")
+                .unwrap(),
+        );
+        bar.set_message(filename.to_string());
+        bar
+    }
+
+    pub fn aggregate(&self) -> Option<ProgressBar> {
+        self.aggregate.clone()
+    }
+
+    pub fn grow_aggregate(&self, extra_bytes: u64) {
+        if let Some(aggregate) = &self.aggregate {
+            aggregate.inc_length(extra_bytes);
+        }
+    }
+
+    pub fn finish_file(&self, bar: ProgressBar) {
+        if self.multi.is_some() {
+            bar.finish_and_clear();
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use crate::crypto::Key;
+use crate::db::{CacheEntry, FileCache};
+use crate::error::{MynkError, retry};
+use crate::mynkignore;
+use crate::progress::SyncProgress;
+use crate::{
+    FileEntry, build_local_state, delete_file, download_file, fetch_server_files, identity_ids,
+    upload_file,
+};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc as std_mpsc;
+use std::time::Duration;
+use tokio::sync::mpsc as tokio_mpsc;
+
+/// How long to wait after the last filesystem event in a burst before
+/// acting on it. Editors that save via a temp-file-then-rename dance
+/// otherwise trigger several events per save.
+pub const DEFAULT_DEBOUNCE_MS: u64 = 500;
+
+/// How often to poll `/structure` for changes made from elsewhere (another
+/// client, or a direct edit on the server) while watching. Local events
+/// are handled immediately through the debounced channel above; this is
+/// only for the direction a filesystem watcher can't see on its own.
+const RECONCILE_INTERVAL: Duration = Duration::from_secs(30);
+
+fn relative_path(root_dir: &Path, path: &Path) -> Option<String> {
+    Some(
+        path.strip_prefix(root_dir)
+            .ok()?
+            .to_string_lossy()
+            .replace('\\', "/"),
+    )
+}
+
+/// Watches `root_dir` for filesystem changes and keeps it synced with
+/// `uri` incrementally: only the paths touched by each debounced burst
+/// of events are re-hashed and uploaded/deleted, rather than re-walking
+/// and re-hashing the whole tree on every change. Since a filesystem
+/// watcher only sees the local side, changes made on the server (or by
+/// another client) are picked up separately by polling `/structure`
+/// every [`RECONCILE_INTERVAL`].
+pub async fn watch(
+    uri: &str,
+    root_dir: PathBuf,
+    quiet: bool,
+    debounce: Duration,
+    encrypt_key: Option<Key>,
+) -> Result<(), MynkError> {
+    let cache = FileCache::open(&root_dir)?;
+    cache.migrate_from_json(&root_dir, &root_dir.join(".mynk-state.json"))?;
+    let ignore = mynkignore::load(&root_dir);
+    let mut index: HashMap<String, FileEntry> =
+        build_local_state(&root_dir, &cache, encrypt_key.as_ref(), &ignore)?
+            .into_iter()
+            .map(|f| (f.filename.clone(), f))
+            .collect();
+
+    let (raw_tx, raw_rx) = std_mpsc::channel::<Event>();
+    let mut watcher = RecommendedWatcher::new(
+        move |res: notify::Result<Event>| {
+            if let Ok(event) = res {
+                let _ = raw_tx.send(event);
+            }
+        },
+        notify::Config::default(),
+    )?;
+    watcher.watch(&root_dir, RecursiveMode::Recursive)?;
+
+    let (batch_tx, mut batch_rx) = tokio_mpsc::unbounded_channel::<HashSet<PathBuf>>();
+    let debounce_root = root_dir.clone();
+    std::thread::spawn(move || debounce_events(raw_rx, batch_tx, &debounce_root, debounce));
+
+    println!("Watching {} for changes...", root_dir.display());
+    let sync_progress = SyncProgress::new(quiet, 0);
+    let mut reconcile_interval = tokio::time::interval(RECONCILE_INTERVAL);
+    reconcile_interval.tick().await; // first tick fires immediately
+
+    loop {
+        tokio::select! {
+            paths = batch_rx.recv() => {
+                let Some(paths) = paths else { break };
+                for path in paths {
+            let Some(relative) = relative_path(&root_dir, &path) else {
+                continue;
+            };
+            if relative == ".mynk"
+                || relative == ".mynk-key"
+                || relative == ".mynkignore"
+                || relative.starts_with(".mynk-cache")
+            {
+                continue;
+            }
+            if mynkignore::is_ignored(&ignore, &path, path.is_dir()) {
+                continue;
+            }
+
+            if path.is_file() {
+                let (data, chunk_refs) = match crate::chunker::split_file(&path) {
+                    Ok(v) => v,
+                    Err(err) => {
+                        eprintln!("Skipping {}: {}", relative, err);
+                        continue;
+                    }
+                };
+                let chunks = match identity_ids(&data, &chunk_refs, encrypt_key.as_ref()) {
+                    Ok(v) => v,
+                    Err(err) => {
+                        eprintln!("Skipping {}: {}", relative, err);
+                        continue;
+                    }
+                };
+                let unchanged = index
+                    .get(&relative)
+                    .map(|existing| existing.chunks == chunks)
+                    .unwrap_or(false);
+                if unchanged {
+                    continue;
+                }
+
+                let version = index.get(&relative).map(|f| f.version + 1).unwrap_or(1);
+                let entry = FileEntry {
+                    filename: relative.clone(),
+                    chunks,
+                    version,
+                };
+                let upload_result = retry("upload", || {
+                    upload_file(uri, &root_dir, &entry, &sync_progress, encrypt_key.as_ref())
+                })
+                .await;
+                if let Err(err) = upload_result {
+                    eprintln!("Failed to upload {}: {}", relative, err);
+                    continue;
+                }
+                if let Ok(metadata) = std::fs::metadata(&path) {
+                    let mtime_secs = metadata
+                        .modified()
+                        .ok()
+                        .and_then(|m| m.duration_since(std::time::UNIX_EPOCH).ok())
+                        .map(|d| d.as_secs() as i64)
+                        .unwrap_or(0);
+                    let _ = cache.put(
+                        &relative,
+                        &CacheEntry {
+                            chunks: entry.chunks.clone(),
+                            version: entry.version,
+                            size: metadata.len(),
+                            mtime_secs,
+                        },
+                    );
+                }
+                index.insert(relative, entry);
+            } else if index.remove(&relative).is_some() {
+                if let Err(err) = retry("delete", || delete_file(uri, &relative)).await {
+                    eprintln!("Failed to delete {}: {}", relative, err);
+                }
+                let _ = cache.remove(&relative);
+            }
+                }
+            }
+            _ = reconcile_interval.tick() => {
+                if let Err(err) = reconcile_with_server(
+                    uri,
+                    &root_dir,
+                    &cache,
+                    &mut index,
+                    &sync_progress,
+                    encrypt_key.as_ref(),
+                )
+                .await
+                {
+                    eprintln!("Failed to reconcile with server: {}", err);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Fetches `/structure` and downloads any file that's new or has a newer
+/// version on the server than what `index` last saw, updating `index` and
+/// the cache to match. Mirrors the download half of `sync_files`, scoped
+/// to what a filesystem watcher can't detect on its own.
+async fn reconcile_with_server(
+    uri: &str,
+    root_dir: &Path,
+    cache: &FileCache,
+    index: &mut HashMap<String, FileEntry>,
+    sync_progress: &SyncProgress,
+    encrypt_key: Option<&Key>,
+) -> Result<(), MynkError> {
+    let server_files = fetch_server_files(uri).await?;
+
+    for server_file in server_files {
+        let is_new_or_newer = index
+            .get(&server_file.filename)
+            .map(|local| server_file.version > local.version)
+            .unwrap_or(true);
+        if !is_new_or_newer {
+            continue;
+        }
+
+        let result = retry("download", || {
+            download_file(uri, root_dir, &server_file, sync_progress, encrypt_key)
+        })
+        .await;
+        if let Err(err) = result {
+            eprintln!("Failed to download {}: {}", server_file.filename, err);
+            continue;
+        }
+        println!("Downloaded file from server: {}", server_file.filename);
+
+        if let Ok(metadata) = std::fs::metadata(root_dir.join(&server_file.filename)) {
+            let mtime_secs = metadata
+                .modified()
+                .ok()
+                .and_then(|m| m.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0);
+            let _ = cache.put(
+                &server_file.filename,
+                &CacheEntry {
+                    chunks: server_file.chunks.clone(),
+                    version: server_file.version,
+                    size: metadata.len(),
+                    mtime_secs,
+                },
+            );
+        }
+        index.insert(server_file.filename.clone(), server_file);
+    }
+    Ok(())
+}
+
+/// Collects raw filesystem events off `raw_rx` and flushes the set of
+/// affected paths through `batch_tx` once `debounce` has elapsed since
+/// the last event, coalescing rapid edits into one batch.
+fn debounce_events(
+    raw_rx: std_mpsc::Receiver<Event>,
+    batch_tx: tokio_mpsc::UnboundedSender<HashSet<PathBuf>>,
+    root_dir: &Path,
+    debounce: Duration,
+) {
+    let mut pending: HashSet<PathBuf> = HashSet::new();
+    let cache_dir = root_dir.join(".mynk-cache");
+    loop {
+        match raw_rx.recv_timeout(debounce) {
+            Ok(event) => {
+                if matches!(
+                    event.kind,
+                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                ) {
+                    for path in event.paths {
+                        if path.starts_with(root_dir) && !path.starts_with(&cache_dir) {
+                            pending.insert(path);
+                        }
+                    }
+                }
+            }
+            Err(std_mpsc::RecvTimeoutError::Timeout) => {
+                if !pending.is_empty() {
+                    let batch = std::mem::take(&mut pending);
+                    if batch_tx.send(batch).is_err() {
+                        return;
+                    }
+                }
+            }
+            Err(std_mpsc::RecvTimeoutError::Disconnected) => return,
+        }
+    }
+}